@@ -0,0 +1,317 @@
+//! Gateway-side JWT enforcement: signature/expiry verification plus a
+//! revocation list ("JRL" - JWT Revocation List), so a compromised token can
+//! be killed without restarting the gateway or touching the upstream.
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::env;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Set to "true"/"1" to preserve today's pass-through behavior (forward the
+/// token unchecked) while migrating clients onto the new verification path.
+const AUTH_BYPASS_ENV: &str = "GATEWAY_AUTH_BYPASS";
+/// PEM (RSA/EC public key) or HMAC secret used to verify token signatures.
+const AUTH_VERIFY_KEY_ENV: &str = "GATEWAY_AUTH_VERIFY_KEY";
+/// One of the `jsonwebtoken::Algorithm` variant names, e.g. "RS256", "HS256".
+/// Defaults to RS256.
+const AUTH_ALGORITHM_ENV: &str = "GATEWAY_AUTH_ALGORITHM";
+/// Local file to (re)load the revocation list from.
+const AUTH_JRL_FILE_ENV: &str = "GATEWAY_AUTH_JRL_FILE";
+/// URL to (re)load the revocation list from, polled every refresh interval.
+const AUTH_JRL_URL_ENV: &str = "GATEWAY_AUTH_JRL_URL";
+/// Revocation list refresh interval in seconds. Defaults to 60.
+const AUTH_JRL_REFRESH_SECS_ENV: &str = "GATEWAY_AUTH_JRL_REFRESH_SECS";
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct Claims {
+    // Never read directly: `jsonwebtoken::decode` validates expiry against
+    // this registered claim itself before we ever see the deserialized
+    // value, so its only job is to require the claim's presence.
+    #[allow(dead_code)]
+    exp: usize,
+    #[serde(default)]
+    jti: Option<String>,
+}
+
+/// Why a request was rejected at the edge.
+#[derive(Debug)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken(String),
+    Revoked,
+}
+
+impl AuthError {
+    pub fn status(&self) -> axum::http::StatusCode {
+        match self {
+            AuthError::MissingToken | AuthError::InvalidToken(_) => axum::http::StatusCode::UNAUTHORIZED,
+            AuthError::Revoked => axum::http::StatusCode::FORBIDDEN,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            AuthError::MissingToken => "Missing authentication token".to_string(),
+            AuthError::InvalidToken(reason) => format!("Invalid token: {}", reason),
+            AuthError::Revoked => "Token has been revoked".to_string(),
+        }
+    }
+}
+
+/// In-memory set of revoked `jti` claims, refreshable from a file or URL
+/// without restarting the gateway.
+struct RevocationList {
+    jtis: RwLock<HashSet<String>>,
+}
+
+impl RevocationList {
+    fn new() -> Self {
+        Self { jtis: RwLock::new(HashSet::new()) }
+    }
+
+    fn is_revoked(&self, jti: &str) -> bool {
+        self.jtis.read().unwrap().contains(jti)
+    }
+
+    fn replace(&self, jtis: HashSet<String>) {
+        *self.jtis.write().unwrap() = jtis;
+    }
+}
+
+fn parse_jrl(body: &str) -> HashSet<String> {
+    body.lines().map(|line| line.trim().to_string()).filter(|line| !line.is_empty()).collect()
+}
+
+/// Verifies tokens at the edge and enforces the revocation list. Built once
+/// at startup from env config and shared via `AppState`.
+pub struct AuthGate {
+    bypass: bool,
+    decoding_key: Option<DecodingKey>,
+    algorithm: Algorithm,
+    revocation_list: RevocationList,
+    jrl_file: Option<String>,
+    jrl_url: Option<String>,
+}
+
+impl AuthGate {
+    /// Build the gate from environment variables. Never panics: a missing
+    /// verification key simply means every token fails verification (unless
+    /// bypass is enabled), which is safer than silently trusting tokens.
+    pub fn from_env() -> Self {
+        let bypass = matches!(
+            env::var(AUTH_BYPASS_ENV).unwrap_or_default().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes"
+        );
+
+        let algorithm = env::var(AUTH_ALGORITHM_ENV)
+            .ok()
+            .and_then(|s| parse_algorithm(&s))
+            .unwrap_or(Algorithm::RS256);
+
+        let decoding_key = env::var(AUTH_VERIFY_KEY_ENV).ok().and_then(|key| {
+            build_decoding_key(&key, algorithm)
+                .map_err(|e| tracing::error!("Failed to load {}: {}", AUTH_VERIFY_KEY_ENV, e))
+                .ok()
+        });
+
+        if !bypass && decoding_key.is_none() {
+            tracing::warn!(
+                "{} not set or invalid - all tokens will be rejected until it is configured (or {}=true)",
+                AUTH_VERIFY_KEY_ENV, AUTH_BYPASS_ENV
+            );
+        }
+
+        Self {
+            bypass,
+            decoding_key,
+            algorithm,
+            revocation_list: RevocationList::new(),
+            jrl_file: env::var(AUTH_JRL_FILE_ENV).ok(),
+            jrl_url: env::var(AUTH_JRL_URL_ENV).ok(),
+        }
+    }
+
+    /// Periodically reload the revocation list from the configured file or
+    /// URL. Runs forever; spawn it as a background task.
+    pub async fn run_jrl_refresh_loop(&self) {
+        if self.jrl_file.is_none() && self.jrl_url.is_none() {
+            return;
+        }
+
+        let refresh_secs: u64 = env::var(AUTH_JRL_REFRESH_SECS_ENV)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(60);
+
+        loop {
+            if let Err(e) = self.refresh_jrl_once().await {
+                tracing::warn!("Failed to refresh JWT revocation list: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(refresh_secs)).await;
+        }
+    }
+
+    async fn refresh_jrl_once(&self) -> Result<(), String> {
+        let body = if let Some(url) = &self.jrl_url {
+            reqwest::get(url)
+                .await
+                .map_err(|e| e.to_string())?
+                .text()
+                .await
+                .map_err(|e| e.to_string())?
+        } else if let Some(path) = &self.jrl_file {
+            tokio::fs::read_to_string(path).await.map_err(|e| e.to_string())?
+        } else {
+            return Ok(());
+        };
+
+        let jtis = parse_jrl(&body);
+        tracing::info!("Loaded {} revoked JWT ids into the JRL", jtis.len());
+        self.revocation_list.replace(jtis);
+        Ok(())
+    }
+
+    /// Verify a token's signature, expiry, and revocation status. `None`
+    /// means no token was presented at all.
+    pub fn check(&self, token: Option<&str>) -> Result<(), AuthError> {
+        if self.bypass {
+            return Ok(());
+        }
+
+        let token = token.ok_or(AuthError::MissingToken)?;
+
+        let decoding_key = self
+            .decoding_key
+            .as_ref()
+            .ok_or_else(|| AuthError::InvalidToken("gateway has no verification key configured".to_string()))?;
+
+        let validation = Validation::new(self.algorithm);
+        let data = decode::<Claims>(token, decoding_key, &validation)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+
+        if let Some(jti) = &data.claims.jti {
+            if self.revocation_list.is_revoked(jti) {
+                return Err(AuthError::Revoked);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_algorithm(name: &str) -> Option<Algorithm> {
+    match name.to_ascii_uppercase().as_str() {
+        "HS256" => Some(Algorithm::HS256),
+        "HS384" => Some(Algorithm::HS384),
+        "HS512" => Some(Algorithm::HS512),
+        "RS256" => Some(Algorithm::RS256),
+        "RS384" => Some(Algorithm::RS384),
+        "RS512" => Some(Algorithm::RS512),
+        "ES256" => Some(Algorithm::ES256),
+        "ES384" => Some(Algorithm::ES384),
+        _ => None,
+    }
+}
+
+fn build_decoding_key(key: &str, algorithm: Algorithm) -> Result<DecodingKey, String> {
+    match algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => Ok(DecodingKey::from_secret(key.as_bytes())),
+        Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 => {
+            DecodingKey::from_rsa_pem(key.as_bytes()).map_err(|e| e.to_string())
+        }
+        Algorithm::ES256 | Algorithm::ES384 => {
+            DecodingKey::from_ec_pem(key.as_bytes()).map_err(|e| e.to_string())
+        }
+        other => Err(format!("unsupported algorithm: {:?}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const SECRET: &str = "test-secret";
+
+    fn gate_with_key() -> AuthGate {
+        AuthGate {
+            bypass: false,
+            decoding_key: Some(DecodingKey::from_secret(SECRET.as_bytes())),
+            algorithm: Algorithm::HS256,
+            revocation_list: RevocationList::new(),
+            jrl_file: None,
+            jrl_url: None,
+        }
+    }
+
+    fn token_with_exp(exp_offset_secs: i64, jti: Option<&str>) -> String {
+        let exp = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 + exp_offset_secs) as usize;
+        let claims = Claims { exp, jti: jti.map(|s| s.to_string()) };
+        encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(SECRET.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn bypass_accepts_even_without_a_token() {
+        let gate = AuthGate {
+            bypass: true,
+            decoding_key: None,
+            algorithm: Algorithm::HS256,
+            revocation_list: RevocationList::new(),
+            jrl_file: None,
+            jrl_url: None,
+        };
+        assert!(gate.check(None).is_ok());
+    }
+
+    #[test]
+    fn missing_token_is_rejected() {
+        let gate = gate_with_key();
+        assert!(matches!(gate.check(None), Err(AuthError::MissingToken)));
+    }
+
+    #[test]
+    fn missing_verification_key_rejects_every_token() {
+        let gate = AuthGate {
+            bypass: false,
+            decoding_key: None,
+            algorithm: Algorithm::HS256,
+            revocation_list: RevocationList::new(),
+            jrl_file: None,
+            jrl_url: None,
+        };
+        let token = token_with_exp(3600, None);
+        assert!(matches!(gate.check(Some(&token)), Err(AuthError::InvalidToken(_))));
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let gate = gate_with_key();
+        let token = token_with_exp(-3600, None);
+        assert!(matches!(gate.check(Some(&token)), Err(AuthError::InvalidToken(_))));
+    }
+
+    #[test]
+    fn valid_unrevoked_token_is_accepted() {
+        let gate = gate_with_key();
+        let token = token_with_exp(3600, Some("abc-123"));
+        assert!(gate.check(Some(&token)).is_ok());
+    }
+
+    #[test]
+    fn revoked_token_is_rejected() {
+        let gate = gate_with_key();
+        gate.revocation_list.replace(HashSet::from(["abc-123".to_string()]));
+        let token = token_with_exp(3600, Some("abc-123"));
+        assert!(matches!(gate.check(Some(&token)), Err(AuthError::Revoked)));
+    }
+
+    #[test]
+    fn parse_jrl_trims_and_skips_blank_lines() {
+        let jtis = parse_jrl("abc-123\n  def-456  \n\n  \nghi-789");
+        assert_eq!(jtis, HashSet::from(["abc-123".to_string(), "def-456".to_string(), "ghi-789".to_string()]));
+    }
+}
@@ -1,33 +1,57 @@
 use axum::{
     Router,
-    routing::get,
-    extract::State,
-    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Uri, Method, Request, header},
+    routing::any,
+    extract::{FromRequestParts, Path, State, ws::{WebSocket, WebSocketUpgrade}},
+    http::{Extensions, HeaderMap, HeaderName, HeaderValue, StatusCode, Uri, Method, Request, header},
     response::{IntoResponse, Response},
     body::Body,
 };
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use bytes::Bytes;
-use futures_util::{StreamExt, SinkExt, stream::{SplitSink, SplitStream}};
+use futures_util::{StreamExt, SinkExt};
 use hyper_util::client::legacy::{Client as HyperClient, connect::HttpConnector};
 use hyper_util::rt::TokioExecutor;
+use rand::RngCore;
+use sha1::{Digest, Sha1};
+use subtle::ConstantTimeEq;
 use std::sync::Arc;
 use std::env;
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::signal;
 use tokio::net::TcpStream;
 use tokio_tungstenite::{
     WebSocketStream, MaybeTlsStream,
-    connect_async, tungstenite::Message,
+    connect_async, tungstenite::Message as TungsteniteMessage,
 };
+use tower::Service;
 use tower_http::compression::CompressionLayer;
 use tower_http::timeout::TimeoutLayer;
 use url::Url;
 
+mod auth;
+mod mux;
+
+/// GUID appended to the WebSocket key before hashing, per RFC 6455 section 1.3.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B16";
+
+/// URL of a dedicated mux-aware upstream to multiplex client WebSocket
+/// tunnels over, instead of dialing one upstream connection per client.
+/// Unset (the default) disables multiplexing entirely.
+///
+/// This is deliberately a separate setting from `PRIVATE_BACKEND_URL`: the
+/// mux wire format (see `mux.rs`) is a private framing scheme that only a
+/// peer built to speak it can understand, so it must never be pointed at
+/// the generic HTTP/WebSocket backend every other route proxies to.
+const WS_MUX_UPSTREAM_URL_ENV: &str = "GATEWAY_WS_MUX_UPSTREAM_URL";
+
 // Shared state with hyper client for zero-copy streaming
 struct AppState {
     client: HyperClient<hyper_rustls::HttpsConnector<HttpConnector>, Body>,
     target_url: Arc<str>,
     hf_token: Arc<str>,
+    auth_gate: auth::AuthGate,
+    mux: Arc<tokio::sync::RwLock<Option<mux::MuxHandle>>>,
 }
 
 #[tokio::main]
@@ -68,19 +92,41 @@ async fn main() {
         .retry_canceled_requests(true)
         .build(https);
 
+    let auth_gate = auth::AuthGate::from_env();
+    let mux = Arc::new(tokio::sync::RwLock::new(None));
+
     let state = Arc::new(AppState {
         client,
         target_url,
         hf_token,
+        auth_gate,
+        mux,
     });
 
+    // Keep the JWT revocation list fresh without requiring a restart.
+    {
+        let state = state.clone();
+        tokio::spawn(async move {
+            state.auth_gate.run_jrl_refresh_loop().await;
+        });
+    }
+
+    // If a dedicated mux upstream is configured, keep it connected for the
+    // life of the process, reconnecting with backoff whenever it drops
+    // instead of leaving `state.mux` stuck on a dead handle.
+    if let Ok(mux_url) = env::var(WS_MUX_UPSTREAM_URL_ENV) {
+        let mux_slot = state.mux.clone();
+        tokio::spawn(run_mux_supervisor(mux_url, mux_slot));
+    }
+
     // Build router with middleware
     // Note: WebSocket routes should NOT have compression/timeout middleware
     let app = Router::new()
-        .route("/gateway-health", get(gateway_health))
+        .route("/gateway-health", any(gateway_health))
+        .route("/gateway-tcp/:host/:port", any(gateway_tcp_handler))
         .fallback(proxy_handler)
         .layer(CompressionLayer::new())
-        .layer(TimeoutLayer::new(Duration::from_secs(30)))
+        .layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, Duration::from_secs(30)))
         .with_state(state);
 
     // Bind to port
@@ -94,11 +140,64 @@ async fn main() {
     tracing::info!("📡 Proxying to: {}", env::var("PRIVATE_BACKEND_URL").unwrap());
     tracing::info!("🔌 WebSocket proxy: ENABLED");
 
-    // Graceful shutdown
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .expect("Server failed");
+    serve(listener, app).await;
+}
+
+/// Serve `app` with a manual hyper_util accept loop instead of `axum::serve`.
+///
+/// `axum::serve` has no way to turn on `SETTINGS_ENABLE_CONNECT_PROTOCOL`, but
+/// per RFC 8441 a server must advertise that setting before a client may ever
+/// send an HTTP/2 extended `CONNECT` with a `:protocol` pseudo-header -
+/// without it, `is_websocket_upgrade`'s HTTP/2 branch never fires. This
+/// mirrors axum's own "WebSockets over HTTP/2" low-level server example.
+async fn serve(listener: tokio::net::TcpListener, app: Router) {
+    let graceful = hyper_util::server::graceful::GracefulShutdown::new();
+    let mut shutdown = std::pin::pin!(shutdown_signal());
+
+    loop {
+        let (stream, _remote_addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => {
+                tracing::info!("No longer accepting new connections, draining in-flight ones");
+                break;
+            }
+        };
+
+        let tower_service = app.clone();
+        let watcher = graceful.watcher();
+        tokio::spawn(async move {
+            let io = hyper_util::rt::TokioIo::new(stream);
+            let hyper_service = hyper::service::service_fn(move |request: Request<hyper::body::Incoming>| {
+                tower_service.clone().call(request)
+            });
+
+            let mut builder = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new());
+            builder.http2().enable_connect_protocol();
+
+            let conn = watcher.watch(builder.serve_connection_with_upgrades(io, hyper_service));
+            if let Err(e) = conn.await {
+                tracing::debug!("connection closed: {}", e);
+            }
+        });
+    }
+
+    // Wait for all in-flight connections watched above to finish (or a grace
+    // period to elapse), instead of tearing down the runtime out from under
+    // them the instant the accept loop stops.
+    tokio::select! {
+        _ = graceful.shutdown() => {
+            tracing::info!("All connections drained, shutting down");
+        }
+        _ = tokio::time::sleep(Duration::from_secs(30)) => {
+            tracing::warn!("Timed out waiting for in-flight connections to drain, shutting down anyway");
+        }
+    }
 }
 
 async fn shutdown_signal() {
@@ -139,13 +238,25 @@ async fn gateway_health() -> impl IntoResponse {
     )
 }
 
-/// Check if request is a WebSocket upgrade request
-fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
-    headers
+/// Check if request is a WebSocket upgrade request - either a classic
+/// HTTP/1.1 `Upgrade: websocket`, or an HTTP/2 extended CONNECT (RFC 8441)
+/// with a `:protocol` pseudo-header of `websocket`.
+fn is_websocket_upgrade(method: &Method, headers: &HeaderMap, extensions: &Extensions) -> bool {
+    let http1_upgrade = headers
         .get(header::UPGRADE)
         .and_then(|v| v.to_str().ok())
         .map(|v| v.eq_ignore_ascii_case("websocket"))
-        .unwrap_or(false)
+        .unwrap_or(false);
+
+    // hyper surfaces the `:protocol` pseudo-header of an HTTP/2 extended
+    // CONNECT as a request extension rather than a regular header.
+    let http2_connect = method == Method::CONNECT
+        && extensions
+            .get::<hyper::ext::Protocol>()
+            .map(|p| p.as_str().eq_ignore_ascii_case("websocket"))
+            .unwrap_or(false);
+
+    http1_upgrade || http2_connect
 }
 
 /// Extract JWT token from request headers
@@ -153,8 +264,8 @@ fn extract_token(headers: &HeaderMap) -> Option<String> {
     // Check Authorization header first
     if let Some(auth) = headers.get(header::AUTHORIZATION) {
         if let Ok(auth_str) = auth.to_str() {
-            if auth_str.starts_with("Bearer ") {
-                return Some(auth_str[7..].to_string());
+            if let Some(token) = auth_str.strip_prefix("Bearer ") {
+                return Some(token.to_string());
             }
         }
     }
@@ -195,53 +306,99 @@ fn build_ws_url(target_url: &str, path: &str, query: Option<&str>) -> Result<Url
     Url::parse(&full_url).map_err(|e| e.to_string())
 }
 
-/// Handle WebSocket proxy - bidirectional message forwarding
+/// Keep `slot` populated with a live [`mux::MuxHandle`] for `mux_url` for as
+/// long as the process runs: connect, hand the handle to callers via `slot`,
+/// then wait for the connection to drop and clear `slot` back to `None` so
+/// nobody is handed a dead handle, before reconnecting with backoff.
+async fn run_mux_supervisor(mux_url: String, slot: Arc<tokio::sync::RwLock<Option<mux::MuxHandle>>>) {
+    let mut backoff = Duration::from_secs(1);
+    const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+    loop {
+        match connect_async(mux_url.as_str()).await {
+            Ok((stream, _)) => {
+                tracing::info!("🧵 WebSocket multiplexing connected against mux upstream: {}", mux_url);
+                backoff = Duration::from_secs(1);
+
+                let (handle, closed) = mux::spawn(stream);
+                *slot.write().await = Some(handle);
+
+                let _ = closed.await;
+                tracing::warn!("🧵 Mux upstream connection lost - reconnecting");
+                *slot.write().await = None;
+            }
+            Err(e) => {
+                tracing::error!("Mux: failed to connect to {}: {} - retrying in {:?}", mux_url, e, backoff);
+            }
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Handle WebSocket proxy - upgrades the client connection and pumps frames
+/// bidirectionally between the client and the upstream WebSocket.
 async fn handle_websocket_proxy(
+    ws: WebSocketUpgrade,
     state: Arc<AppState>,
     uri: Uri,
     headers: HeaderMap,
 ) -> Response {
-    let path = uri.path();
-    let query = uri.query();
-    
+    let path = uri.path().to_string();
+    let query = uri.query().map(|q| q.to_string());
+
     tracing::info!("🔌 WebSocket upgrade request for: {}", path);
-    
+
+    // Extract token from client request
+    let token = extract_token(&headers);
+
+    // Reject invalid/expired/revoked tokens at the edge, before ever dialing upstream
+    if let Err(e) = state.auth_gate.check(token.as_deref()) {
+        tracing::warn!("🚫 Rejected WebSocket upgrade for {}: {}", path, e.message());
+        return (e.status(), e.message()).into_response();
+    }
+
+    // When a mux upstream is configured and currently connected, share it
+    // instead of opening a fresh per-client connection to `target_url`.
+    if let Some(mux_handle) = state.mux.read().await.clone() {
+        return ws.on_upgrade(move |socket| pump_muxed_websocket(socket, mux_handle, path, query));
+    }
+
     // Build upstream WebSocket URL
-    let ws_url = match build_ws_url(&state.target_url, path, query) {
+    let ws_url = match build_ws_url(&state.target_url, &path, query.as_deref()) {
         Ok(url) => url,
         Err(e) => {
             tracing::error!("Failed to build WebSocket URL: {}", e);
             return (StatusCode::BAD_REQUEST, "Invalid WebSocket URL").into_response();
         }
     };
-    
-    // Extract token from client request
-    let token = extract_token(&headers);
-    
+
     // Build request for upstream with token in protocol header
+    let ws_key = generate_ws_key();
     let mut request = tokio_tungstenite::tungstenite::http::Request::builder()
         .uri(ws_url.as_str())
         .header("Host", ws_url.host_str().unwrap_or(""))
         .header(header::UPGRADE, "websocket")
         .header(header::CONNECTION, "Upgrade")
         .header("Sec-WebSocket-Version", "13")
-        .header("Sec-WebSocket-Key", generate_ws_key());
-    
+        .header("Sec-WebSocket-Key", &ws_key);
+
     // Add token to protocol header (same format as client)
     if let Some(ref t) = token {
         request = request.header("Sec-WebSocket-Protocol", format!("access_token, {}", t));
     }
-    
+
     // Add HF token for private space access
     if !state.hf_token.is_empty() {
         request = request.header(header::AUTHORIZATION, format!("Bearer {}", state.hf_token));
     }
-    
+
     // Forward original origin if present
     if let Some(origin) = headers.get(header::ORIGIN) {
         request = request.header(header::ORIGIN, origin);
     }
-    
+
     let request = match request.body(()) {
         Ok(r) => r,
         Err(e) => {
@@ -249,74 +406,439 @@ async fn handle_websocket_proxy(
             return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build request").into_response();
         }
     };
-    
-    // Connect to upstream WebSocket
+
+    // Connect to upstream WebSocket before upgrading the client, so a dead
+    // backend surfaces as a normal HTTP error instead of a silently dead tunnel.
     tracing::info!("🔗 Connecting to upstream WebSocket: {}", ws_url);
-    
-    match connect_async(request).await {
-        Ok((upstream_ws, response)) => {
-            tracing::info!("✅ Upstream WebSocket connected (status: {})", response.status());
-            
-            // Return 101 Switching Protocols to client
-            // In a real implementation, we'd use axum's WebSocket extractor
-            // For now, return a message indicating WebSocket is ready
-            let mut builder = Response::builder()
-                .status(StatusCode::SWITCHING_PROTOCOLS)
-                .header(header::UPGRADE, "websocket")
-                .header(header::CONNECTION, "Upgrade");
-            
-            // Echo back the protocol if we received one
-            if token.is_some() {
-                builder = builder.header("Sec-WebSocket-Protocol", "access_token");
-            }
-            
-            if let Some(accept_key) = response.headers().get("sec-websocket-accept") {
-                builder = builder.header("Sec-WebSocket-Accept", accept_key);
-            }
-            
-            builder.body(Body::empty()).unwrap_or_else(|_| {
-                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response").into_response()
-            })
-        }
+
+    let (upstream_ws, response) = match connect_async(request).await {
+        Ok(pair) => pair,
         Err(e) => {
             tracing::error!("❌ Failed to connect to upstream WebSocket: {}", e);
-            (
+            return (
                 StatusCode::BAD_GATEWAY,
                 format!("WebSocket connection failed: {}", e),
-            ).into_response()
+            ).into_response();
         }
+    };
+
+    // Verify the handshake per RFC 6455: the accept value must be derived
+    // from the exact key we sent, or a misbehaving/compromised upstream could
+    // complete a bogus handshake undetected.
+    let expected_accept = compute_ws_accept(&ws_key);
+    let actual_accept = response
+        .headers()
+        .get("sec-websocket-accept")
+        .and_then(|v| v.to_str().ok());
+    if actual_accept != Some(expected_accept.as_str()) {
+        tracing::error!(
+            "❌ Upstream WebSocket handshake failed Sec-WebSocket-Accept validation (expected {}, got {:?})",
+            expected_accept, actual_accept
+        );
+        return (StatusCode::BAD_GATEWAY, "Upstream WebSocket handshake validation failed").into_response();
     }
+
+    tracing::info!("✅ Upstream WebSocket connected (status: {})", response.status());
+
+    ws.on_upgrade(move |socket| pump_websocket(socket, upstream_ws, path))
 }
 
-/// Generate a random WebSocket key
-fn generate_ws_key() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .subsec_nanos();
-    base64_encode(&nanos.to_le_bytes())
+/// Relay frames between the upgraded client socket and the upstream WebSocket
+/// until either side closes.
+async fn pump_websocket(
+    client_ws: WebSocket,
+    upstream_ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    path: String,
+) {
+    let (mut client_tx, mut client_rx) = client_ws.split();
+    let (mut upstream_tx, mut upstream_rx) = upstream_ws.split();
+
+    loop {
+        tokio::select! {
+            msg = client_rx.next() => {
+                match msg {
+                    Some(Ok(msg)) => {
+                        let closing = matches!(msg, axum::extract::ws::Message::Close(_));
+                        if let Err(e) = upstream_tx.send(to_upstream_message(msg)).await {
+                            tracing::warn!("WebSocket {}: failed to forward to upstream: {}", path, e);
+                            break;
+                        }
+                        if closing {
+                            break;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        tracing::warn!("WebSocket {}: client connection error: {}", path, e);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+            msg = upstream_rx.next() => {
+                match msg {
+                    Some(Ok(msg)) => {
+                        let closing = matches!(msg, TungsteniteMessage::Close(_));
+                        if let Err(e) = client_tx.send(to_client_message(msg)).await {
+                            tracing::warn!("WebSocket {}: failed to forward to client: {}", path, e);
+                            break;
+                        }
+                        if closing {
+                            break;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        tracing::warn!("WebSocket {}: upstream connection error: {}", path, e);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+
+    let _ = client_tx.close().await;
+    let _ = upstream_tx.close().await;
+    tracing::info!("WebSocket {}: tunnel closed", path);
 }
 
-/// Simple base64 encoding for WebSocket key
-fn base64_encode(data: &[u8]) -> String {
-    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut result = String::new();
-    for chunk in data.chunks(3) {
-        let mut n: u32 = 0;
-        for (i, &byte) in chunk.iter().enumerate() {
-            n |= (byte as u32) << (16 - i * 8);
+/// Relay frames between the upgraded client socket and a logical stream of
+/// the shared, multiplexed upstream connection until either side closes.
+async fn pump_muxed_websocket(
+    client_ws: WebSocket,
+    mux_handle: mux::MuxHandle,
+    path: String,
+    query: Option<String>,
+) {
+    let target = match &query {
+        Some(q) => format!("{}?{}", path, q),
+        None => path.clone(),
+    };
+
+    let mut mux_stream = match mux_handle.open_stream(Bytes::from(target.clone().into_bytes())).await {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("Mux: failed to open logical stream for {}: {}", target, e);
+            return;
         }
-        let padding = 3 - chunk.len();
-        for i in 0..(4 - padding) {
-            let idx = ((n >> (18 - i * 6)) & 0x3F) as usize;
-            result.push(ALPHABET[idx] as char);
+    };
+
+    let (mut client_tx, mut client_rx) = client_ws.split();
+
+    loop {
+        tokio::select! {
+            msg = client_rx.next() => {
+                match msg {
+                    Some(Ok(axum::extract::ws::Message::Binary(data))) => {
+                        if mux_stream.send(data.into()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(axum::extract::ws::Message::Text(text))) => {
+                        if mux_stream.send(Bytes::from(text.to_string().into_bytes())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(axum::extract::ws::Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {} // Ping/Pong carry no meaning on the mux wire protocol
+                    Some(Err(e)) => {
+                        tracing::warn!("Mux tunnel {}: client connection error: {}", target, e);
+                        break;
+                    }
+                }
+            }
+            data = mux_stream.rx.recv() => {
+                match data {
+                    Some(bytes) => {
+                        if client_tx.send(axum::extract::ws::Message::Binary(bytes.to_vec())).await.is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
         }
-        for _ in 0..padding {
-            result.push('=');
+    }
+
+    let _ = client_tx.close().await;
+    tracing::info!("Mux tunnel {}: closed", target);
+}
+
+/// Translate a client-side (axum) WebSocket message into the tungstenite
+/// message type used for the upstream connection.
+fn to_upstream_message(msg: axum::extract::ws::Message) -> TungsteniteMessage {
+    use axum::extract::ws::Message as AxumMessage;
+    match msg {
+        AxumMessage::Text(t) => TungsteniteMessage::Text(t.to_string()),
+        AxumMessage::Binary(b) => TungsteniteMessage::Binary(b),
+        AxumMessage::Ping(p) => TungsteniteMessage::Ping(p),
+        AxumMessage::Pong(p) => TungsteniteMessage::Pong(p),
+        AxumMessage::Close(frame) => TungsteniteMessage::Close(frame.map(|f| {
+            tokio_tungstenite::tungstenite::protocol::CloseFrame {
+                code: f.code.into(),
+                reason: f.reason.to_string().into(),
+            }
+        })),
+    }
+}
+
+/// Translate an upstream (tungstenite) WebSocket message into the axum
+/// message type used for the client connection.
+fn to_client_message(msg: TungsteniteMessage) -> axum::extract::ws::Message {
+    use axum::extract::ws::{CloseFrame, Message as AxumMessage};
+    match msg {
+        TungsteniteMessage::Text(t) => AxumMessage::Text(t.to_string()),
+        TungsteniteMessage::Binary(b) => AxumMessage::Binary(b),
+        TungsteniteMessage::Ping(p) => AxumMessage::Ping(p),
+        TungsteniteMessage::Pong(p) => AxumMessage::Pong(p),
+        TungsteniteMessage::Close(frame) => AxumMessage::Close(frame.map(|f| CloseFrame {
+            code: f.code.into(),
+            reason: f.reason.to_string().into(),
+        })),
+        TungsteniteMessage::Frame(_) => AxumMessage::Binary(Vec::new()),
+    }
+}
+
+/// Env var holding a comma-separated allow-list of `host:port` targets that
+/// `/gateway-tcp/{host}/{port}` is permitted to dial. Unset or empty means
+/// nothing is allowed, so the tunnel can't be used as an open relay.
+const TCP_ALLOWLIST_ENV: &str = "GATEWAY_TCP_ALLOWLIST";
+
+/// Check a requested `host:port` target against the comma-separated
+/// allow-list in the given env var.
+fn is_host_port_allowed(list_env: &str, host: &str, port: u16) -> bool {
+    let target = format!("{}:{}", host, port);
+    env::var(list_env)
+        .map(|list| list.split(',').map(|s| s.trim()).any(|allowed| allowed == target))
+        .unwrap_or(false)
+}
+
+/// Handle `/gateway-tcp/{host}/{port}` - bridges a client WebSocket to a raw
+/// TCP connection so non-HTTP backends (databases, SSH, ...) can be tunneled
+/// through the same HTTPS front door.
+async fn gateway_tcp_handler(
+    State(state): State<Arc<AppState>>,
+    Path((host, port)): Path<(String, u16)>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if let Err(e) = state.auth_gate.check(extract_token(&headers).as_deref()) {
+        tracing::warn!("🚫 Rejected TCP tunnel request to {}:{}: {}", host, port, e.message());
+        return (e.status(), e.message()).into_response();
+    }
+
+    if !is_host_port_allowed(TCP_ALLOWLIST_ENV, &host, port) {
+        tracing::warn!("🚫 Rejected TCP tunnel request to disallowed target {}:{}", host, port);
+        return (StatusCode::FORBIDDEN, "Target not in TCP allow-list").into_response();
+    }
+
+    tracing::info!("🔌 TCP tunnel request for {}:{}", host, port);
+    ws.on_upgrade(move |socket| pump_tcp_tunnel(socket, host, port))
+}
+
+/// Relay binary frames between an upgraded client WebSocket and a plain TCP
+/// connection until either side closes.
+async fn pump_tcp_tunnel(client_ws: WebSocket, host: String, port: u16) {
+    let target = format!("{}:{}", host, port);
+
+    let tcp_stream = match TcpStream::connect(&target).await {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("TCP tunnel: failed to connect to {}: {}", target, e);
+            return;
+        }
+    };
+
+    let (mut tcp_read, mut tcp_write) = tcp_stream.into_split();
+    let (mut client_tx, mut client_rx) = client_ws.split();
+    let mut read_buf = [0u8; 8192];
+
+    loop {
+        tokio::select! {
+            msg = client_rx.next() => {
+                match msg {
+                    Some(Ok(axum::extract::ws::Message::Binary(data))) => {
+                        if let Err(e) = tcp_write.write_all(&data).await {
+                            tracing::warn!("TCP tunnel {}: write failed: {}", target, e);
+                            break;
+                        }
+                    }
+                    Some(Ok(axum::extract::ws::Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {
+                        // Text/Ping/Pong carry no meaning on a raw TCP tunnel
+                    }
+                    Some(Err(e)) => {
+                        tracing::warn!("TCP tunnel {}: client connection error: {}", target, e);
+                        break;
+                    }
+                }
+            }
+            n = tcp_read.read(&mut read_buf) => {
+                match n {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        let frame = axum::extract::ws::Message::Binary(read_buf[..n].to_vec());
+                        if let Err(e) = client_tx.send(frame).await {
+                            tracing::warn!("TCP tunnel {}: failed to forward to client: {}", target, e);
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("TCP tunnel {}: read failed: {}", target, e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = client_tx.close().await;
+    tracing::info!("TCP tunnel {}: closed", target);
+}
+
+/// Env var holding a comma-separated allow-list of `host:port` targets the
+/// forward proxy (`CONNECT`) is permitted to dial.
+const FORWARD_PROXY_ALLOWLIST_ENV: &str = "GATEWAY_PROXY_ALLOWLIST";
+/// Env vars holding the expected `Basic` credentials for forward-proxy auth.
+const FORWARD_PROXY_BASIC_USER_ENV: &str = "GATEWAY_PROXY_USER";
+const FORWARD_PROXY_BASIC_PASS_ENV: &str = "GATEWAY_PROXY_PASS";
+/// Env var holding the expected `Bearer` token for forward-proxy auth.
+const FORWARD_PROXY_BEARER_TOKEN_ENV: &str = "GATEWAY_PROXY_TOKEN";
+
+/// Handle an HTTP forward-proxy `CONNECT host:port` request: authenticate via
+/// `Proxy-Authorization`, then hijack the connection and splice it to a raw
+/// TCP connection dialed at the requested authority.
+async fn handle_connect_proxy(mut request: Request<Body>) -> Response {
+    let Some(authority) = request.uri().authority().map(|a| a.to_string()) else {
+        return (StatusCode::BAD_REQUEST, "CONNECT requires an authority").into_response();
+    };
+
+    if let Err(resp) = authenticate_proxy_request(request.headers()) {
+        return *resp;
+    }
+
+    let Some((host, port)) = parse_authority(&authority) else {
+        return (StatusCode::BAD_REQUEST, "Invalid CONNECT authority").into_response();
+    };
+
+    if !is_host_port_allowed(FORWARD_PROXY_ALLOWLIST_ENV, &host, port) {
+        tracing::warn!("🚫 Rejected CONNECT tunnel request to disallowed target {}", authority);
+        return (StatusCode::FORBIDDEN, "Target not in forward-proxy allow-list").into_response();
+    }
+
+    tracing::info!("🔀 CONNECT tunnel request for {}", authority);
+    let on_upgrade = hyper::upgrade::on(&mut request);
+
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => {
+                if let Err(e) = splice_connect_tunnel(upgraded, &host, port).await {
+                    tracing::warn!("CONNECT tunnel {}: {}", authority, e);
+                }
+            }
+            Err(e) => tracing::error!("CONNECT tunnel {}: upgrade failed: {}", authority, e),
+        }
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .body(Body::empty())
+        .unwrap_or_else(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response").into_response())
+}
+
+/// Validate the client's `Proxy-Authorization` header against the configured
+/// `Basic` credentials or `Bearer` token. Returns the `407` response to send
+/// back on any failure, boxed since `Response` is much larger than the `Ok`
+/// case and this is returned from a `Result`.
+fn authenticate_proxy_request(headers: &HeaderMap) -> Result<(), Box<Response>> {
+    let unauthorized = || {
+        Box::new((
+            StatusCode::PROXY_AUTHENTICATION_REQUIRED,
+            [(header::PROXY_AUTHENTICATE, HeaderValue::from_static("Basic realm=\"speedgateway\""))],
+            "Proxy authentication required",
+        ).into_response())
+    };
+
+    let Some(auth) = headers.get(header::PROXY_AUTHORIZATION).and_then(|v| v.to_str().ok()) else {
+        return Err(unauthorized());
+    };
+
+    if let Some(encoded) = auth.strip_prefix("Basic ") {
+        let expected_user = env::var(FORWARD_PROXY_BASIC_USER_ENV).unwrap_or_default();
+        let expected_pass = env::var(FORWARD_PROXY_BASIC_PASS_ENV).unwrap_or_default();
+        let ok = BASE64
+            .decode(encoded)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|creds| creds.split_once(':').map(|(u, p)| (u.to_string(), p.to_string())))
+            .is_some_and(|(user, pass)| {
+                !expected_user.is_empty()
+                    && !expected_pass.is_empty()
+                    && ct_str_eq(&user, &expected_user)
+                    && ct_str_eq(&pass, &expected_pass)
+            });
+        if ok {
+            return Ok(());
+        }
+    } else if let Some(token) = auth.strip_prefix("Bearer ") {
+        let expected_token = env::var(FORWARD_PROXY_BEARER_TOKEN_ENV).unwrap_or_default();
+        if !expected_token.is_empty() && ct_str_eq(token, &expected_token) {
+            return Ok(());
         }
     }
-    result
+
+    Err(unauthorized())
+}
+
+/// Constant-time string equality, so comparing client-supplied proxy
+/// credentials against the configured secret can't be used as a
+/// byte-by-byte timing oracle. The length check below is not constant-time,
+/// but a length mismatch leaks only the length, never any byte of the secret.
+fn ct_str_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && bool::from(a.as_bytes().ct_eq(b.as_bytes()))
+}
+
+/// Split a `host:port` CONNECT authority into its parts.
+fn parse_authority(authority: &str) -> Option<(String, u16)> {
+    let (host, port) = authority.rsplit_once(':')?;
+    let port: u16 = port.parse().ok()?;
+    Some((host.to_string(), port))
+}
+
+/// Splice an upgraded `CONNECT` client connection to a freshly-dialed TCP
+/// connection at `host:port`, copying bytes in both directions.
+async fn splice_connect_tunnel(
+    upgraded: hyper::upgrade::Upgraded,
+    host: &str,
+    port: u16,
+) -> Result<(), String> {
+    let target = format!("{}:{}", host, port);
+    let mut tcp_stream = TcpStream::connect(&target)
+        .await
+        .map_err(|e| format!("failed to connect to {}: {}", target, e))?;
+    let mut upgraded_io = hyper_util::rt::TokioIo::new(upgraded);
+
+    tokio::io::copy_bidirectional(&mut upgraded_io, &mut tcp_stream)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("splice error: {}", e))
+}
+
+/// Generate a `Sec-WebSocket-Key`: 16 cryptographically-random bytes,
+/// base64-encoded, per RFC 6455 section 1.3.
+fn generate_ws_key() -> String {
+    let mut key_bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut key_bytes);
+    BASE64.encode(key_bytes)
+}
+
+/// Compute the expected `Sec-WebSocket-Accept` value for a given
+/// `Sec-WebSocket-Key`, per RFC 6455 section 1.3:
+/// `base64(SHA1(key + "258EAFA5-E914-47DA-95CA-C5AB0DC85B16"))`.
+fn compute_ws_accept(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    BASE64.encode(hasher.finalize())
 }
 
 // Headers to skip when forwarding
@@ -328,16 +850,40 @@ static SKIP_HEADERS: &[&str] = &[
 // Streaming proxy handler - zero-copy where possible
 async fn proxy_handler(
     State(state): State<Arc<AppState>>,
-    method: Method,
-    uri: Uri,
-    headers: HeaderMap,
-    body: Body,
+    request: Request<Body>,
 ) -> Response {
-    // Check for WebSocket upgrade
-    if is_websocket_upgrade(&headers) {
-        return handle_websocket_proxy(state, uri, headers).await;
+    // A plain `CONNECT host:port` (no HTTP/2 `:protocol` pseudo-header) is a
+    // forward-proxy tunnel request, handled entirely separately from the
+    // reverse-proxy path below.
+    let is_h2_websocket_connect = request.method() == Method::CONNECT
+        && request
+            .extensions()
+            .get::<hyper::ext::Protocol>()
+            .map(|p| p.as_str().eq_ignore_ascii_case("websocket"))
+            .unwrap_or(false);
+    if request.method() == Method::CONNECT && !is_h2_websocket_connect {
+        return handle_connect_proxy(request).await;
     }
-    
+
+    let (mut parts, body) = request.into_parts();
+    let method = parts.method.clone();
+    let uri = parts.uri.clone();
+    let headers = parts.headers.clone();
+
+    // Check for WebSocket upgrade (HTTP/1.1 Upgrade or HTTP/2 extended CONNECT)
+    if is_websocket_upgrade(&method, &headers, &parts.extensions) {
+        return match WebSocketUpgrade::from_request_parts(&mut parts, &state).await {
+            Ok(ws) => handle_websocket_proxy(ws, state, uri, headers).await,
+            Err(rejection) => rejection.into_response(),
+        };
+    }
+
+    // Reject invalid/expired/revoked tokens at the edge, before ever dialing upstream
+    if let Err(e) = state.auth_gate.check(extract_token(&headers).as_deref()) {
+        tracing::warn!("🚫 Rejected request for {}: {}", uri.path(), e.message());
+        return (e.status(), e.message()).into_response();
+    }
+
     // Regular HTTP proxy
     let path = uri.path();
     let target_url = match uri.query() {
@@ -438,4 +984,35 @@ async fn proxy_handler(
             ).into_response()
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_ws_key_is_16_bytes_base64_encoded() {
+        let key = generate_ws_key();
+        let decoded = BASE64.decode(&key).unwrap();
+        assert_eq!(decoded.len(), 16);
+    }
+
+    #[test]
+    fn generated_ws_keys_are_not_reused() {
+        assert_ne!(generate_ws_key(), generate_ws_key());
+    }
+
+    #[test]
+    fn compute_ws_accept_matches_rfc6455_example() {
+        // Worked example straight out of RFC 6455 section 1.3.
+        assert_eq!(compute_ws_accept("dGhlIHNhbXBsZSBub25jZQ=="), "ErhEfSMVJvC3FTevCFG+qKlPkws=");
+    }
+
+    #[test]
+    fn compute_ws_accept_is_deterministic_for_a_generated_key() {
+        let key = generate_ws_key();
+        // Just asserts the function is deterministic for a given key - the
+        // actual handshake-acceptance check lives on the client/upstream side.
+        assert_eq!(compute_ws_accept(&key), compute_ws_accept(&key));
+    }
 }
\ No newline at end of file
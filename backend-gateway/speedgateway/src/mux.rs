@@ -0,0 +1,297 @@
+//! Multiplexes many logical client tunnels over a single persistent upstream
+//! WebSocket connection, so concurrent clients don't each pay the cost of a
+//! fresh `connect_async` handshake.
+//!
+//! This framing is only understood by a peer that speaks it - a generic
+//! HTTP/WebSocket backend (e.g. the Gradio/HF Space `PRIVATE_BACKEND_URL`
+//! every other route proxies to unmodified) has no idea what an `Open`/
+//! `Data`/`Close` frame is and will never produce a matching reply. Point
+//! [`spawn`] at a dedicated mux-aware upstream, never at `PRIVATE_BACKEND_URL`.
+//!
+//! Wire format: each multiplexed frame is carried as one upstream WebSocket
+//! `Binary` message with a fixed header:
+//!
+//! ```text
+//! +-----------+--------+-------------+---------+
+//! | stream_id | flag   | payload_len | payload |
+//! |  u32 BE   |  u8    |   u32 BE    |  bytes  |
+//! +-----------+--------+-------------+---------+
+//! ```
+//!
+//! `flag` is one of [`FrameFlag::Open`]/[`FrameFlag::Data`]/[`FrameFlag::Close`].
+//! A new client tunnel allocates a stream id and sends an `Open` frame (whose
+//! payload carries the target the upstream should route the stream to);
+//! `Close` tears the logical stream down without touching the shared
+//! connection.
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt, stream::{SplitSink, SplitStream}};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, mpsc, oneshot};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::Message};
+
+/// Bound on each logical stream's inbound channel, so a slow client can't
+/// make the gateway buffer unbounded data. The demux task never waits on a
+/// full channel: a stream that fills it is closed (see `run_demux`), so the
+/// shared upstream connection is never held back by one stream.
+const STREAM_CHANNEL_CAPACITY: usize = 64;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameFlag {
+    Open = 0,
+    Data = 1,
+    Close = 2,
+}
+
+impl FrameFlag {
+    fn from_u8(b: u8) -> Option<Self> {
+        match b {
+            0 => Some(FrameFlag::Open),
+            1 => Some(FrameFlag::Data),
+            2 => Some(FrameFlag::Close),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MuxFrame {
+    stream_id: u32,
+    flag: FrameFlag,
+    payload: Bytes,
+}
+
+impl MuxFrame {
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(9 + self.payload.len());
+        buf.put_u32(self.stream_id);
+        buf.put_u8(self.flag as u8);
+        buf.put_u32(self.payload.len() as u32);
+        buf.put_slice(&self.payload);
+        buf.to_vec()
+    }
+
+    fn decode(mut data: Bytes) -> Result<Self, String> {
+        if data.len() < 9 {
+            return Err("frame too short for header".to_string());
+        }
+        let stream_id = data.get_u32();
+        let flag = FrameFlag::from_u8(data.get_u8()).ok_or("unknown frame flag")?;
+        let len = data.get_u32() as usize;
+        if data.len() != len {
+            return Err("frame payload length does not match header".to_string());
+        }
+        Ok(Self { stream_id, flag, payload: data })
+    }
+}
+
+type UpstreamSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+type UpstreamSource = SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+
+struct MuxInner {
+    sink: Mutex<UpstreamSink>,
+    streams: Mutex<HashMap<u32, mpsc::Sender<Bytes>>>,
+    next_stream_id: AtomicU32,
+}
+
+/// A single logical stream's view onto the shared upstream connection.
+/// Dropping it sends a `Close` frame so the upstream can free its side.
+pub struct MuxStream {
+    pub stream_id: u32,
+    pub rx: mpsc::Receiver<Bytes>,
+    handle: MuxHandle,
+}
+
+impl MuxStream {
+    pub async fn send(&self, data: Bytes) -> Result<(), String> {
+        self.handle.send_data(self.stream_id, data).await
+    }
+}
+
+impl Drop for MuxStream {
+    fn drop(&mut self) {
+        self.handle.close_stream(self.stream_id);
+    }
+}
+
+/// Cloneable handle to a running mux task. Opening a stream allocates a
+/// fresh id and sends an `Open` frame carrying `target` as its payload.
+#[derive(Clone)]
+pub struct MuxHandle {
+    inner: Arc<MuxInner>,
+}
+
+impl MuxHandle {
+    pub async fn open_stream(&self, target: Bytes) -> Result<MuxStream, String> {
+        let stream_id = self.inner.next_stream_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        self.inner.streams.lock().await.insert(stream_id, tx);
+
+        self.write_frame(MuxFrame { stream_id, flag: FrameFlag::Open, payload: target }).await?;
+
+        Ok(MuxStream { stream_id, rx, handle: self.clone() })
+    }
+
+    async fn send_data(&self, stream_id: u32, payload: Bytes) -> Result<(), String> {
+        self.write_frame(MuxFrame { stream_id, flag: FrameFlag::Data, payload }).await
+    }
+
+    fn close_stream(&self, stream_id: u32) {
+        let inner = self.inner.clone();
+        tokio::spawn(async move {
+            inner.streams.lock().await.remove(&stream_id);
+            let frame = MuxFrame { stream_id, flag: FrameFlag::Close, payload: Bytes::new() };
+            let mut sink = inner.sink.lock().await;
+            let _ = sink.send(Message::Binary(frame.encode())).await;
+        });
+    }
+
+    async fn write_frame(&self, frame: MuxFrame) -> Result<(), String> {
+        let mut sink = self.inner.sink.lock().await;
+        sink.send(Message::Binary(frame.encode())).await.map_err(|e| e.to_string())
+    }
+}
+
+/// Take ownership of the single upstream WebSocket connection and spawn the
+/// task that demultiplexes incoming frames to the per-stream channel
+/// registered by [`MuxHandle::open_stream`]. Clone the returned handle once
+/// per logical stream.
+///
+/// The returned [`oneshot::Receiver`] resolves once the upstream connection
+/// drops and all logical streams have been torn down - callers that keep the
+/// handle around (e.g. in shared state) must watch it and stop handing the
+/// handle to new callers, since every `open_stream`/`send` on it will fail
+/// from that point on.
+pub fn spawn(upstream: WebSocketStream<MaybeTlsStream<TcpStream>>) -> (MuxHandle, oneshot::Receiver<()>) {
+    let (sink, source) = upstream.split();
+    let inner = Arc::new(MuxInner {
+        sink: Mutex::new(sink),
+        streams: Mutex::new(HashMap::new()),
+        next_stream_id: AtomicU32::new(1),
+    });
+    let handle = MuxHandle { inner: inner.clone() };
+
+    let (closed_tx, closed_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        run_demux(inner, source).await;
+        let _ = closed_tx.send(());
+    });
+
+    (handle, closed_rx)
+}
+
+async fn run_demux(inner: Arc<MuxInner>, mut source: UpstreamSource) {
+    while let Some(msg) = source.next().await {
+        let msg = match msg {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::warn!("Mux: upstream connection error: {}", e);
+                break;
+            }
+        };
+
+        let data: Bytes = match msg {
+            Message::Binary(b) => b.into(),
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let frame = match MuxFrame::decode(data) {
+            Ok(f) => f,
+            Err(e) => {
+                tracing::warn!("Mux: dropping malformed frame: {}", e);
+                continue;
+            }
+        };
+
+        match frame.flag {
+            FrameFlag::Open => {
+                // Upstream-initiated streams aren't used by this gateway today.
+            }
+            FrameFlag::Data => {
+                let sender = inner.streams.lock().await.get(&frame.stream_id).cloned();
+                if let Some(sender) = sender {
+                    // `run_demux` is the only reader of the shared upstream
+                    // connection, so it must never await a full per-stream
+                    // channel - one slow/stalled client would freeze delivery
+                    // for every other stream sharing the connection. Drop the
+                    // offending stream instead of blocking on it.
+                    match sender.try_send(frame.payload) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            tracing::warn!(
+                                "Mux: stream {} can't keep up - closing it instead of stalling the shared connection",
+                                frame.stream_id
+                            );
+                            inner.streams.lock().await.remove(&frame.stream_id);
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => {
+                            inner.streams.lock().await.remove(&frame.stream_id);
+                        }
+                    }
+                }
+            }
+            FrameFlag::Close => {
+                inner.streams.lock().await.remove(&frame.stream_id);
+            }
+        }
+    }
+
+    tracing::info!("Mux: upstream connection closed, tearing down all logical streams");
+    inner.streams.lock().await.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let frame = MuxFrame { stream_id: 7, flag: FrameFlag::Data, payload: Bytes::from_static(b"hello") };
+        let encoded = frame.encode();
+        let decoded = MuxFrame::decode(Bytes::from(encoded)).unwrap();
+        assert_eq!(decoded.stream_id, 7);
+        assert_eq!(decoded.flag, FrameFlag::Data);
+        assert_eq!(decoded.payload, Bytes::from_static(b"hello"));
+    }
+
+    #[test]
+    fn encode_decode_empty_payload() {
+        let frame = MuxFrame { stream_id: 1, flag: FrameFlag::Close, payload: Bytes::new() };
+        let decoded = MuxFrame::decode(Bytes::from(frame.encode())).unwrap();
+        assert_eq!(decoded.stream_id, 1);
+        assert_eq!(decoded.flag, FrameFlag::Close);
+        assert!(decoded.payload.is_empty());
+    }
+
+    #[test]
+    fn decode_rejects_too_short_frame() {
+        let err = MuxFrame::decode(Bytes::from_static(b"short")).unwrap_err();
+        assert_eq!(err, "frame too short for header");
+    }
+
+    #[test]
+    fn decode_rejects_length_mismatch() {
+        let mut buf = BytesMut::new();
+        buf.put_u32(1);
+        buf.put_u8(FrameFlag::Data as u8);
+        buf.put_u32(10); // claims 10 bytes of payload
+        buf.put_slice(b"abc"); // only provides 3
+        let err = MuxFrame::decode(buf.freeze()).unwrap_err();
+        assert_eq!(err, "frame payload length does not match header");
+    }
+
+    #[test]
+    fn decode_rejects_unknown_flag() {
+        let mut buf = BytesMut::new();
+        buf.put_u32(1);
+        buf.put_u8(0xFF);
+        buf.put_u32(0);
+        let err = MuxFrame::decode(buf.freeze()).unwrap_err();
+        assert_eq!(err, "unknown frame flag");
+    }
+}